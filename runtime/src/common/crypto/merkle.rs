@@ -0,0 +1,352 @@
+//! Incremental, append-only Merkle accumulator with O(log n) inclusion proofs.
+//!
+//! Leaves are appended one at a time and only the rightmost path of the tree is ever
+//! recomputed, so [`MerkleTree::append`] touches O(log n) nodes instead of rebuilding the tree.
+//! Node hashes are domain separated (distinct prefixes for leaf vs. internal nodes, over
+//! SHA3-256) to rule out second-preimage attacks that reinterpret a leaf hash as an internal
+//! node hash or vice versa.
+//!
+//! An unbalanced tree (size not a power of two) is handled the same way RFC 6962's Merkle Tree
+//! Hash handles it: the hash of a range of leaves is defined by recursively splitting it at the
+//! largest power of two smaller than its size. This has the effect of promoting a lone
+//! right-most node unchanged up through the layers until a later append gives it a sibling to
+//! pair with.
+
+use sha3::{Digest, Sha3_256};
+
+use super::hash::Hash;
+
+/// Domain separation prefix for a leaf node hash.
+const LEAF_PREFIX: u8 = 0x00;
+
+/// Domain separation prefix for an internal node hash.
+const NODE_PREFIX: u8 = 0x01;
+
+/// Byte size of a serialized [`Hash`].
+const HASH_SIZE: usize = 32;
+
+/// An incremental, append-only Merkle tree.
+///
+/// Stored as a vector of layers: `layers[0]` holds every leaf hash in append order, and
+/// `layers[i + 1]` holds the hash of every *complete* pair of adjacent `layers[i]` nodes, also
+/// in append order. A node only ever joins a layer once its sibling exists, so a lone
+/// right-most node simply has no parent yet; it is folded into a larger subtree, unchanged,
+/// the next time [`MerkleTree::root`] or [`MerkleTree::proof`] needs it.
+#[derive(Clone, Debug, Default)]
+pub struct MerkleTree {
+    layers: Vec<Vec<Hash>>,
+    size: u64,
+}
+
+/// An inclusion proof that the leaf appended at `index` is part of the tree of size `size`
+/// whose root is known.
+#[derive(Clone, Debug)]
+pub struct MerkleProof {
+    /// Index of the leaf this proof is for.
+    pub index: u64,
+    /// Number of leaves in the tree this proof was generated against.
+    pub size: u64,
+    /// Sibling hashes needed to recompute the root, ordered from the leaf's level up to the
+    /// root.
+    pub siblings: Vec<Hash>,
+}
+
+impl MerkleTree {
+    /// An empty tree.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of leaves appended so far.
+    pub fn len(&self) -> u64 {
+        self.size
+    }
+
+    /// Whether the tree has no leaves.
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Append a new leaf over `entry` and return its index.
+    pub fn append(&mut self, entry: &[u8]) -> u64 {
+        let index = self.size;
+        let mut hash = Self::leaf_hash(entry);
+
+        for level in 0.. {
+            if level == self.layers.len() {
+                self.layers.push(Vec::new());
+            }
+            self.layers[level].push(hash);
+
+            // A node at an even position (1-indexed: odd length) has no sibling yet, so its
+            // parent can't be formed until a later append pairs it up. Stop climbing here.
+            if self.layers[level].len() % 2 != 0 {
+                break;
+            }
+
+            let left = self.layers[level][self.layers[level].len() - 2].clone();
+            let right = self.layers[level][self.layers[level].len() - 1].clone();
+            hash = Self::node_hash(&left, &right);
+        }
+
+        self.size += 1;
+        index
+    }
+
+    /// The current root hash. The hash of an empty leaf if no leaves have been appended.
+    pub fn root(&self) -> Hash {
+        if self.size == 0 {
+            return Self::leaf_hash(&[]);
+        }
+        self.subtree_hash(0, self.size)
+    }
+
+    /// An inclusion proof for the leaf at `index`.
+    ///
+    /// Panics if `index` is out of range; callers are expected to check against
+    /// [`MerkleTree::len`] first, as with a normal out-of-bounds index.
+    pub fn proof(&self, index: u64) -> MerkleProof {
+        assert!(index < self.size, "merkle: index out of range");
+        let mut siblings = Vec::new();
+        self.collect_siblings(index, 0, self.size, &mut siblings);
+        MerkleProof {
+            index,
+            size: self.size,
+            siblings,
+        }
+    }
+
+    /// Verify that `entry`, at `proof.index`, is included under `root`.
+    ///
+    /// Stateless: does not require the tree itself, only the proof and the claimed root, so a
+    /// light client can verify it having only ever seen published roots.
+    pub fn verify(root: &Hash, index: u64, entry: &[u8], proof: &MerkleProof) -> bool {
+        if proof.index != index || index >= proof.size {
+            return false;
+        }
+
+        let leaf = Self::leaf_hash(entry);
+        let mut used = 0;
+        match Self::fold_path(index, 0, proof.size, &proof.siblings, &mut used, leaf) {
+            Some(computed) => used == proof.siblings.len() && computed == *root,
+            None => false,
+        }
+    }
+
+    /// The hash of the complete subtree covering the `size` leaves starting at `start`.
+    ///
+    /// When `size` is a power of two this is a stored layer entry (the subtree is guaranteed
+    /// complete because every `(start, size)` pair reachable from [`MerkleTree::root`] and
+    /// [`MerkleTree::collect_siblings`] comes from recursively splitting `[0, self.size)` at
+    /// power-of-two boundaries, and every complete pair is retained in `layers` once formed).
+    /// Otherwise it is computed on the fly by splitting at the largest power of two smaller
+    /// than `size`, mirroring RFC 6962's Merkle Tree Hash definition.
+    fn subtree_hash(&self, start: u64, size: u64) -> Hash {
+        if size == 1 {
+            return self.layers[0][start as usize].clone();
+        }
+        if size.is_power_of_two() {
+            let level = size.trailing_zeros() as usize;
+            return self.layers[level][(start >> level) as usize].clone();
+        }
+
+        let k = Self::split_point(size);
+        let left = self.subtree_hash(start, k);
+        let right = self.subtree_hash(start + k, size - k);
+        Self::node_hash(&left, &right)
+    }
+
+    /// Collect the sibling hashes needed to prove leaf `m` is in `[start, start + size)`, in
+    /// leaf-to-root order.
+    fn collect_siblings(&self, m: u64, start: u64, size: u64, siblings: &mut Vec<Hash>) {
+        if size == 1 {
+            return;
+        }
+        let k = Self::split_point(size);
+        if m - start < k {
+            self.collect_siblings(m, start, k, siblings);
+            siblings.push(self.subtree_hash(start + k, size - k));
+        } else {
+            self.collect_siblings(m, start + k, size - k, siblings);
+            siblings.push(self.subtree_hash(start, k));
+        }
+    }
+
+    /// Recompute the root along leaf `m`'s path, consuming `siblings` in the same order
+    /// [`Self::collect_siblings`] produced them. Mirrors its recursive split exactly, so the
+    /// two stay in lockstep without needing to record which side each sibling is on. Returns
+    /// `None` if `siblings` runs out before the path does, i.e. a malformed proof.
+    fn fold_path(
+        m: u64,
+        start: u64,
+        size: u64,
+        siblings: &[Hash],
+        used: &mut usize,
+        current: Hash,
+    ) -> Option<Hash> {
+        if size == 1 {
+            return Some(current);
+        }
+        let k = Self::split_point(size);
+        let combined = if m - start < k {
+            let left = Self::fold_path(m, start, k, siblings, used, current)?;
+            let sibling = siblings.get(*used)?.clone();
+            *used += 1;
+            Self::node_hash(&left, &sibling)
+        } else {
+            let right = Self::fold_path(m, start + k, size - k, siblings, used, current)?;
+            let sibling = siblings.get(*used)?.clone();
+            *used += 1;
+            Self::node_hash(&sibling, &right)
+        };
+        Some(combined)
+    }
+
+    /// The largest power of two strictly smaller than `size` (`size` must be at least 2).
+    fn split_point(size: u64) -> u64 {
+        1u64 << (63 - (size - 1).leading_zeros())
+    }
+
+    fn leaf_hash(entry: &[u8]) -> Hash {
+        let mut hasher = Sha3_256::new();
+        hasher.update([LEAF_PREFIX]);
+        hasher.update(entry);
+        Hash::from(Self::finalize(hasher))
+    }
+
+    fn node_hash(left: &Hash, right: &Hash) -> Hash {
+        let mut hasher = Sha3_256::new();
+        hasher.update([NODE_PREFIX]);
+        hasher.update(left.as_ref());
+        hasher.update(right.as_ref());
+        Hash::from(Self::finalize(hasher))
+    }
+
+    fn finalize(hasher: Sha3_256) -> [u8; HASH_SIZE] {
+        let mut out = [0u8; HASH_SIZE];
+        out.copy_from_slice(&hasher.finalize());
+        out
+    }
+
+    /// Serialize the tree so it can be persisted between transactions and reloaded with
+    /// [`MerkleTree::decode`].
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&self.size.to_be_bytes());
+        buf.extend_from_slice(&(self.layers.len() as u64).to_be_bytes());
+        for layer in &self.layers {
+            buf.extend_from_slice(&(layer.len() as u64).to_be_bytes());
+            for hash in layer {
+                buf.extend_from_slice(hash.as_ref());
+            }
+        }
+        buf
+    }
+
+    /// Deserialize a tree previously serialized with [`MerkleTree::encode`].
+    ///
+    /// `data` comes straight out of the (potentially hostile) backing MKVS, so every count is
+    /// checked against what's actually left in the buffer before it's ever used to size an
+    /// allocation: a corrupted or tampered length prefix must fail decoding, not trigger an
+    /// oversized or overflowing `Vec::with_capacity`.
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let mut pos = 0;
+        let size = Self::read_u64(data, &mut pos)?;
+        let layer_count = Self::read_checked_count(data, &mut pos)?;
+
+        let mut layers = Vec::with_capacity(layer_count);
+        for _ in 0..layer_count {
+            let node_count = Self::read_checked_count(data, &mut pos)?;
+            let mut layer = Vec::with_capacity(node_count);
+            for _ in 0..node_count {
+                let raw: [u8; HASH_SIZE] = data.get(pos..pos + HASH_SIZE)?.try_into().ok()?;
+                pos += HASH_SIZE;
+                layer.push(Hash::from(raw));
+            }
+            layers.push(layer);
+        }
+
+        Some(Self { layers, size })
+    }
+
+    fn read_u64(data: &[u8], pos: &mut usize) -> Option<u64> {
+        let raw: [u8; 8] = data.get(*pos..*pos + 8)?.try_into().ok()?;
+        *pos += 8;
+        Some(u64::from_be_bytes(raw))
+    }
+
+    /// Read a `u64` count prefix, rejecting it outright if it claims more elements than could
+    /// possibly still fit in the rest of `data` (every element, layer or node, is at least
+    /// `HASH_SIZE` bytes). Used instead of [`Self::read_u64`] anywhere the count is about to
+    /// size a `Vec::with_capacity`, so an attacker-controlled length can't force an oversized or
+    /// overflowing allocation.
+    fn read_checked_count(data: &[u8], pos: &mut usize) -> Option<usize> {
+        let count = Self::read_u64(data, pos)?;
+        let remaining = data.len().checked_sub(*pos)?;
+        if count > (remaining / HASH_SIZE) as u64 {
+            return None;
+        }
+        Some(count as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MerkleTree;
+
+    #[test]
+    fn round_trips_through_encode_decode() {
+        let mut tree = MerkleTree::new();
+        for i in 0..7u8 {
+            tree.append(&[i]);
+        }
+
+        let decoded = MerkleTree::decode(&tree.encode()).expect("valid encoding must decode");
+        assert_eq!(decoded.root(), tree.root());
+        assert_eq!(decoded.len(), tree.len());
+    }
+
+    #[test]
+    fn proof_verifies_against_root_for_unbalanced_tree() {
+        let mut tree = MerkleTree::new();
+        for i in 0..5u8 {
+            tree.append(&[i]);
+        }
+        let root = tree.root();
+
+        for i in 0..5u64 {
+            let proof = tree.proof(i);
+            assert!(MerkleTree::verify(&root, i, &[i as u8], &proof));
+        }
+    }
+
+    #[test]
+    fn decode_rejects_layer_count_that_overruns_the_buffer() {
+        // size = 1, layer_count = u64::MAX, then nothing else: a tampered/corrupted length
+        // prefix that must be rejected rather than attempted as a huge `Vec::with_capacity`.
+        let mut data = Vec::new();
+        data.extend_from_slice(&1u64.to_be_bytes());
+        data.extend_from_slice(&u64::MAX.to_be_bytes());
+
+        assert!(MerkleTree::decode(&data).is_none());
+    }
+
+    #[test]
+    fn decode_rejects_node_count_that_overruns_the_buffer() {
+        // One real layer header claiming far more nodes than the remainder (a single hash) can
+        // hold. The trailing hash keeps `layer_count = 1` within bounds, so it is the node_count
+        // check that rejects this input.
+        let mut data = Vec::new();
+        data.extend_from_slice(&1u64.to_be_bytes()); // size
+        data.extend_from_slice(&1u64.to_be_bytes()); // layer_count
+        data.extend_from_slice(&u64::MAX.to_be_bytes()); // node_count
+        data.extend_from_slice(&[0u8; super::HASH_SIZE]); // one hash
+
+        assert!(MerkleTree::decode(&data).is_none());
+    }
+
+    #[test]
+    fn decode_rejects_truncated_input() {
+        assert!(MerkleTree::decode(&[0u8; 4]).is_none());
+    }
+}
@@ -0,0 +1,6 @@
+//! Cryptographic primitives.
+pub mod hash;
+pub mod merkle;
+pub mod mrae;
+pub mod secp256k1;
+pub mod signature;
@@ -0,0 +1,41 @@
+//! secp256k1 ECDSA recovery helper.
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use thiserror::Error;
+
+/// Error returned by [`ecdsa_recover`].
+#[derive(Error, Debug)]
+pub enum RecoverError {
+    #[error("invalid recovery id")]
+    BadV,
+
+    #[error("invalid signature r/s values")]
+    BadRS,
+
+    #[error("unable to recover public key from signature")]
+    BadSignature,
+}
+
+/// Recover the 64-byte uncompressed public key (without the leading `0x04` tag) that produced
+/// the given Ethereum-style RSV `signature` over `message_hash`.
+///
+/// Modeled on Substrate's `secp256k1_ecdsa_recover` host function: `signature` is the 65-byte
+/// `r || s || v` triple. `v` is accepted as either `0`/`1` or `27`/`28` (27 is subtracted
+/// whenever `v > 26`).
+pub fn ecdsa_recover(
+    signature: &[u8; 65],
+    message_hash: &[u8; 32],
+) -> Result<[u8; 64], RecoverError> {
+    let v = signature[64];
+    let v = if v > 26 { v - 27 } else { v };
+    let recovery_id = RecoveryId::from_byte(v).ok_or(RecoverError::BadV)?;
+
+    let sig = Signature::from_slice(&signature[..64]).map_err(|_| RecoverError::BadRS)?;
+
+    let verifying_key = VerifyingKey::recover_from_prehash(message_hash, &sig, recovery_id)
+        .map_err(|_| RecoverError::BadSignature)?;
+
+    let point = verifying_key.to_encoded_point(false);
+    let mut pubkey = [0u8; 64];
+    pubkey.copy_from_slice(&point.as_bytes()[1..]);
+    Ok(pubkey)
+}
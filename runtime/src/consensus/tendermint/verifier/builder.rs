@@ -0,0 +1,103 @@
+use std::sync::Arc;
+
+use crate::protocol::Protocol;
+
+use super::{
+    cache::DEFAULT_CACHE_CAPACITY,
+    flow::FlowControlParams,
+    trusted::{TrustRoot, DEFAULT_TRUST_THRESHOLD},
+    NopVerifier, TrustedVerifier, Verifier,
+};
+
+/// Which verification mode a [`VerifierBuilder`] should construct.
+enum Mode {
+    /// Perform no verification at all; trust whatever the host returns.
+    Nop,
+    /// Perform Tendermint bisection verification against a trust anchor.
+    Trusted {
+        trust_root: TrustRoot,
+        trust_threshold: Option<(u64, u64)>,
+        trusting_period_secs: u64,
+    },
+}
+
+/// Builder for constructing a [`Verifier`].
+///
+/// Defaults to the no-op mode; call [`VerifierBuilder::trusted`] to switch to Tendermint
+/// bisection verification against a trust anchor. This is the single entry point for
+/// constructing a verifier as the subsystem grows beyond the original `NopVerifier` type.
+pub struct VerifierBuilder {
+    protocol: Arc<Protocol>,
+    mode: Mode,
+    cache_capacity: usize,
+    flow_params: FlowControlParams,
+}
+
+impl VerifierBuilder {
+    /// Create a new builder defaulting to the no-op verification mode.
+    pub fn new(protocol: Arc<Protocol>) -> Self {
+        Self {
+            protocol,
+            mode: Mode::Nop,
+            cache_capacity: DEFAULT_CACHE_CAPACITY,
+            flow_params: FlowControlParams::default(),
+        }
+    }
+
+    /// Switch to Tendermint bisection verification against the given trust anchor.
+    ///
+    /// `trust_threshold` defaults to 1/3 of the trusted validator set's voting power when not
+    /// set via [`VerifierBuilder::trust_threshold`].
+    pub fn trusted(mut self, trust_root: TrustRoot, trusting_period_secs: u64) -> Self {
+        self.mode = Mode::Trusted {
+            trust_root,
+            trust_threshold: None,
+            trusting_period_secs,
+        };
+        self
+    }
+
+    /// Override the trust threshold used for skip verification (only meaningful in trusted
+    /// mode).
+    pub fn trust_threshold(mut self, num: u64, den: u64) -> Self {
+        if let Mode::Trusted {
+            trust_threshold, ..
+        } = &mut self.mode
+        {
+            *trust_threshold = Some((num, den));
+        }
+        self
+    }
+
+    /// Override the light block / consensus state cache capacity. Only trusted mode uses this
+    /// value; the no-op verifier always uses [`DEFAULT_CACHE_CAPACITY`].
+    pub fn cache_capacity(mut self, capacity: usize) -> Self {
+        self.cache_capacity = capacity;
+        self
+    }
+
+    /// Override the host fetch flow-control parameters.
+    pub fn flow_control(mut self, params: FlowControlParams) -> Self {
+        self.flow_params = params;
+        self
+    }
+
+    /// Build the configured verifier.
+    pub fn build(self) -> Box<dyn Verifier> {
+        match self.mode {
+            Mode::Nop => Box::new(NopVerifier::with_flow_control(self.protocol, self.flow_params)),
+            Mode::Trusted {
+                trust_root,
+                trust_threshold,
+                trusting_period_secs,
+            } => Box::new(TrustedVerifier::with_params(
+                self.protocol,
+                trust_root,
+                trust_threshold.or(Some(DEFAULT_TRUST_THRESHOLD)),
+                trusting_period_secs,
+                self.cache_capacity,
+                self.flow_params,
+            )),
+        }
+    }
+}
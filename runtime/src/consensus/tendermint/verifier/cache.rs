@@ -0,0 +1,75 @@
+use std::num::NonZeroUsize;
+
+use lru::LruCache;
+use tokio::sync::Mutex as AsyncMutex;
+
+use crate::consensus::{state::ConsensusState, LightBlock, HEIGHT_LATEST};
+
+/// Default capacity of the light block / consensus state cache.
+pub const DEFAULT_CACHE_CAPACITY: usize = 128;
+
+/// A bounded cache of fetched light blocks and their derived consensus state, keyed by height.
+///
+/// `HEIGHT_LATEST` is cached separately since, unlike a concrete historic height, its mapped
+/// block changes as the chain advances and must be invalidated whenever a fresher block for that
+/// sentinel height is fetched.
+pub struct VerifierCache {
+    blocks: AsyncMutex<LruCache<u64, LightBlock>>,
+    states: AsyncMutex<LruCache<u64, ConsensusState>>,
+    latest_block: AsyncMutex<Option<LightBlock>>,
+    latest_state: AsyncMutex<Option<ConsensusState>>,
+}
+
+impl VerifierCache {
+    /// Create a new cache with the given capacity (applies independently to blocks and states).
+    pub fn new(capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity.max(1)).unwrap();
+        Self {
+            blocks: AsyncMutex::new(LruCache::new(capacity)),
+            states: AsyncMutex::new(LruCache::new(capacity)),
+            latest_block: AsyncMutex::new(None),
+            latest_state: AsyncMutex::new(None),
+        }
+    }
+
+    /// Look up a cached light block for the given height.
+    pub async fn get_block(&self, height: u64) -> Option<LightBlock> {
+        if height == HEIGHT_LATEST {
+            return self.latest_block.lock().await.clone();
+        }
+        self.blocks.lock().await.get(&height).cloned()
+    }
+
+    /// Insert a freshly fetched light block into the cache.
+    ///
+    /// `actual_height` is the concrete height of the fetched block, which is recorded under its
+    /// own key in addition to (optionally) refreshing the `HEIGHT_LATEST` slot.
+    pub async fn put_block(&self, requested_height: u64, actual_height: u64, block: LightBlock) {
+        if requested_height == HEIGHT_LATEST {
+            *self.latest_block.lock().await = Some(block.clone());
+        }
+        self.blocks.lock().await.put(actual_height, block);
+    }
+
+    /// Look up cached consensus state for the given height.
+    pub async fn get_state(&self, height: u64) -> Option<ConsensusState> {
+        if height == HEIGHT_LATEST {
+            return self.latest_state.lock().await.clone();
+        }
+        self.states.lock().await.get(&height).cloned()
+    }
+
+    /// Insert derived consensus state into the cache.
+    pub async fn put_state(&self, requested_height: u64, actual_height: u64, state: ConsensusState) {
+        if requested_height == HEIGHT_LATEST {
+            *self.latest_state.lock().await = Some(state.clone());
+        }
+        self.states.lock().await.put(actual_height, state);
+    }
+
+    /// Drop the cached `HEIGHT_LATEST` entries, forcing the next lookup to refetch.
+    pub async fn invalidate_latest(&self) {
+        *self.latest_block.lock().await = None;
+        *self.latest_state.lock().await = None;
+    }
+}
@@ -0,0 +1,98 @@
+use std::time::Duration;
+
+use tokio::{sync::Mutex as AsyncMutex, time::Instant};
+
+use super::Error;
+
+/// Cost, in tokens, of a single `HostFetchConsensusBlockRequest` call.
+pub const DEFAULT_BLOCK_FETCH_COST: u64 = 1;
+/// Cost, in tokens, of a single `HostFetchConsensusEventsRequest` call.
+pub const DEFAULT_EVENTS_FETCH_COST: u64 = 1;
+
+/// Parameters controlling the token bucket used to throttle host consensus fetch calls.
+#[derive(Clone, Debug)]
+pub struct FlowControlParams {
+    /// Maximum number of tokens the bucket can hold (i.e. the maximum burst).
+    pub max_burst: u64,
+    /// Number of tokens added to the bucket per second.
+    pub refill_rate: u64,
+    /// Cost of a `HostFetchConsensusBlockRequest` call. `NopVerifier` and `TrustedVerifier` do
+    /// not read this field; they always charge [`DEFAULT_BLOCK_FETCH_COST`].
+    pub block_fetch_cost: u64,
+    /// Cost of a `HostFetchConsensusEventsRequest` call. `NopVerifier` and `TrustedVerifier` do
+    /// not read this field; they always charge [`DEFAULT_EVENTS_FETCH_COST`].
+    pub events_fetch_cost: u64,
+    /// Maximum time a caller will wait for tokens to become available before the call is
+    /// rejected with [`Error::FlowControlExhausted`].
+    pub max_wait: Duration,
+}
+
+impl Default for FlowControlParams {
+    fn default() -> Self {
+        Self {
+            max_burst: 64,
+            refill_rate: 32,
+            block_fetch_cost: DEFAULT_BLOCK_FETCH_COST,
+            events_fetch_cost: DEFAULT_EVENTS_FETCH_COST,
+            max_wait: Duration::from_secs(5),
+        }
+    }
+}
+
+struct BucketState {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// A token-bucket rate limiter guarding outgoing host consensus fetch calls.
+///
+/// Each call costs a configurable number of tokens; the bucket refills continuously at
+/// `refill_rate` tokens/second up to `max_burst`. A caller without enough tokens waits
+/// (polling the bucket) for up to `max_wait` before the call is rejected, so a verifier doing
+/// bisection or range replay degrades to a slower, throttled pace instead of flooding the host
+/// channel.
+pub struct FlowController {
+    params: FlowControlParams,
+    state: AsyncMutex<BucketState>,
+}
+
+impl FlowController {
+    /// Create a new flow controller, starting with a full bucket.
+    pub fn new(params: FlowControlParams) -> Self {
+        let max_burst = params.max_burst as f64;
+        Self {
+            state: AsyncMutex::new(BucketState {
+                tokens: max_burst,
+                last_refill: Instant::now(),
+            }),
+            params,
+        }
+    }
+
+    /// Acquire `cost` tokens, waiting for refill (up to `max_wait`) if the bucket is exhausted.
+    pub async fn acquire(&self, cost: u64) -> Result<(), Error> {
+        let cost = cost as f64;
+        let deadline = Instant::now() + self.params.max_wait;
+
+        loop {
+            {
+                let mut state = self.state.lock().await;
+                let now = Instant::now();
+                let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+                state.tokens =
+                    (state.tokens + elapsed * self.params.refill_rate as f64).min(self.params.max_burst as f64);
+                state.last_refill = now;
+
+                if state.tokens >= cost {
+                    state.tokens -= cost;
+                    return Ok(());
+                }
+            }
+
+            if Instant::now() >= deadline {
+                return Err(Error::FlowControlExhausted);
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+}
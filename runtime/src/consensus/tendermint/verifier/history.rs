@@ -0,0 +1,59 @@
+use async_trait::async_trait;
+use futures::stream::{self, StreamExt};
+
+use crate::consensus::{state::ConsensusState, Event};
+use crate::types::EventKind;
+
+use super::{Error, Verifier};
+
+/// Default number of in-flight host fetches when pipelining a range query.
+pub const DEFAULT_PREFETCH_WINDOW: usize = 16;
+
+/// Extension of [`Verifier`] with batched historic range queries.
+///
+/// This is the natural building block for a runtime that needs to reindex or audit a span of
+/// historic consensus blocks: instead of looping over `state_at`/`events_at` one height at a
+/// time (and paying a host round-trip per height), a caller can request a whole range and have
+/// the underlying fetches pipelined up to a configurable window. Results are returned in height
+/// order as a single `Vec` once every fetch in the range has completed; they are not streamed
+/// to the caller one by one.
+#[async_trait]
+pub trait HistoryProvider: Verifier {
+    /// Return consensus state for every height in `[from, to]`, in order, prefetching up to
+    /// `window` heights concurrently.
+    async fn states_in_range(
+        &self,
+        from: u64,
+        to: u64,
+        window: usize,
+    ) -> Result<Vec<ConsensusState>, Error> {
+        let window = window.max(1);
+        stream::iter((from..=to).map(|height| self.state_at(height)))
+            .buffered(window)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect()
+    }
+
+    /// Return events of `kind` for every height in `[from, to]`, in order, prefetching up to
+    /// `window` heights concurrently. The outer `Vec` is indexed the same as the height range
+    /// (i.e. `result[i]` holds the events for height `from + i`).
+    async fn events_in_range(
+        &self,
+        from: u64,
+        to: u64,
+        kind: EventKind,
+        window: usize,
+    ) -> Result<Vec<Vec<Event>>, Error> {
+        let window = window.max(1);
+        stream::iter((from..=to).map(|height| self.events_at(height, kind)))
+            .buffered(window)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect()
+    }
+}
+
+impl<T: Verifier + ?Sized> HistoryProvider for T {}
@@ -0,0 +1,95 @@
+//! Consensus layer verification.
+use async_trait::async_trait;
+use thiserror::Error;
+
+use crate::consensus::{beacon::EpochTime, roothash::Header, state::ConsensusState, Event, LightBlock};
+use crate::types::EventKind;
+
+pub mod builder;
+pub mod cache;
+pub mod flow;
+pub mod history;
+pub mod noop;
+pub mod trusted;
+pub mod worker;
+
+pub use builder::VerifierBuilder;
+pub use cache::VerifierCache;
+pub use flow::{FlowControlParams, FlowController};
+pub use history::HistoryProvider;
+pub use noop::NopVerifier;
+pub use trusted::TrustedVerifier;
+pub use worker::VerifierHandle;
+
+/// Verifier error.
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("verification failed: {0}")]
+    VerificationFailed(#[source] anyhow::Error),
+
+    #[error("trust root not set")]
+    TrustRootNotSet,
+
+    #[error("header out of trusting period")]
+    HeaderExpired,
+
+    #[error("header is not monotonically increasing")]
+    HeaderNotMonotonic,
+
+    #[error("insufficient voting power overlap")]
+    InsufficientOverlap,
+
+    #[error("insufficient commit voting power")]
+    InsufficientSignedPower,
+
+    #[error("verifier worker is busy")]
+    WorkerBusy,
+
+    #[error("verifier worker has shut down")]
+    WorkerShutDown,
+
+    #[error("flow control limit exceeded")]
+    FlowControlExhausted,
+}
+
+/// Interface for the consensus layer verification service, used by runtimes to securely query
+/// consensus layer state without having to blindly trust the host.
+#[async_trait]
+pub trait Verifier: Send + Sync {
+    /// Sync the verifier up to the given consensus height.
+    async fn sync(&self, height: u64) -> Result<(), Error>;
+
+    /// Verify that the given runtime header is valid at the given consensus layer block and
+    /// return the consensus layer state accessor for that block.
+    async fn verify(
+        &self,
+        consensus_block: LightBlock,
+        runtime_header: Header,
+        epoch: EpochTime,
+    ) -> Result<ConsensusState, Error>;
+
+    /// Verify that the given runtime header is valid for use in queries at the given consensus
+    /// layer block and return the consensus layer state accessor for that block.
+    async fn verify_for_query(
+        &self,
+        consensus_block: LightBlock,
+        runtime_header: Header,
+        epoch: EpochTime,
+    ) -> Result<ConsensusState, Error>;
+
+    /// Return the consensus layer state accessor for the given consensus layer block, skipping
+    /// any verification.
+    async fn unverified_state(&self, consensus_block: LightBlock) -> Result<ConsensusState, Error>;
+
+    /// Return the consensus layer state accessor for the latest verified consensus layer block.
+    async fn latest_state(&self) -> Result<ConsensusState, Error>;
+
+    /// Return the consensus layer state accessor for the given consensus layer height.
+    async fn state_at(&self, height: u64) -> Result<ConsensusState, Error>;
+
+    /// Return events emitted in the given consensus layer block.
+    async fn events_at(&self, height: u64, kind: EventKind) -> Result<Vec<Event>, Error>;
+
+    /// Return the latest known consensus layer height.
+    async fn latest_height(&self) -> Result<u64, Error>;
+}
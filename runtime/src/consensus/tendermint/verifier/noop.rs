@@ -11,7 +11,11 @@ use crate::{
         roothash::Header,
         state::ConsensusState,
         tendermint::decode_light_block,
-        verifier::{self, Error},
+        verifier::{
+            self, cache::DEFAULT_CACHE_CAPACITY, flow::DEFAULT_BLOCK_FETCH_COST,
+            flow::DEFAULT_EVENTS_FETCH_COST, Error, FlowControlParams, FlowController,
+            VerifierCache,
+        },
         Event, LightBlock, HEIGHT_LATEST,
     },
     protocol::Protocol,
@@ -21,12 +25,23 @@ use crate::{
 /// A verifier which performs no verification.
 pub struct NopVerifier {
     protocol: Arc<Protocol>,
+    cache: VerifierCache,
+    flow: FlowController,
 }
 
 impl NopVerifier {
     /// Create a new non-verifying verifier.
     pub fn new(protocol: Arc<Protocol>) -> Self {
-        Self { protocol }
+        Self::with_flow_control(protocol, FlowControlParams::default())
+    }
+
+    /// Create a new non-verifying verifier with custom host fetch flow-control parameters.
+    pub fn with_flow_control(protocol: Arc<Protocol>, flow_params: FlowControlParams) -> Self {
+        Self {
+            protocol,
+            cache: VerifierCache::new(DEFAULT_CACHE_CAPACITY),
+            flow: FlowController::new(flow_params),
+        }
     }
 
     /// Start the non-verifying verifier.
@@ -36,22 +51,33 @@ impl NopVerifier {
     }
 
     async fn fetch_light_block(&self, height: u64) -> Result<LightBlock, Error> {
+        if let Some(block) = self.cache.get_block(height).await {
+            return Ok(block);
+        }
+
+        self.flow.acquire(DEFAULT_BLOCK_FETCH_COST).await?;
         let result = self
             .protocol
             .call_host_async(Body::HostFetchConsensusBlockRequest { height })
             .await
             .map_err(|err| Error::VerificationFailed(err.into()))?;
 
-        match result {
-            Body::HostFetchConsensusBlockResponse { block } => Ok(block),
-            _ => Err(Error::VerificationFailed(anyhow!("bad response from host"))),
-        }
+        let block = match result {
+            Body::HostFetchConsensusBlockResponse { block } => block,
+            _ => return Err(Error::VerificationFailed(anyhow!("bad response from host"))),
+        };
+
+        self.cache.put_block(height, block.height, block.clone()).await;
+        Ok(block)
     }
 }
 
 #[async_trait]
 impl verifier::Verifier for NopVerifier {
     async fn sync(&self, _height: u64) -> Result<(), Error> {
+        // The chain has advanced since the last sync, so the cached `HEIGHT_LATEST` entries are
+        // no longer guaranteed to reflect the actual latest block/state.
+        self.cache.invalidate_latest().await;
         Ok(())
     }
 
@@ -90,11 +116,19 @@ impl verifier::Verifier for NopVerifier {
     }
 
     async fn state_at(&self, height: u64) -> Result<ConsensusState, Error> {
+        if let Some(state) = self.cache.get_state(height).await {
+            return Ok(state);
+        }
+
         let block = self.fetch_light_block(height).await?;
-        self.unverified_state(block).await
+        let actual_height = block.height;
+        let state = self.unverified_state(block).await?;
+        self.cache.put_state(height, actual_height, state.clone()).await;
+        Ok(state)
     }
 
     async fn events_at(&self, height: u64, kind: EventKind) -> Result<Vec<Event>, Error> {
+        self.flow.acquire(DEFAULT_EVENTS_FETCH_COST).await?;
         let result = self
             .protocol
             .call_host_async(Body::HostFetchConsensusEventsRequest(
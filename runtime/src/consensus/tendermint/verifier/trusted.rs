@@ -0,0 +1,442 @@
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use slog::info;
+use tokio::sync::Mutex;
+
+use crate::{
+    common::logger::get_logger,
+    consensus::{
+        beacon::EpochTime,
+        roothash::Header,
+        state::ConsensusState,
+        tendermint::decode_light_block,
+        verifier::{
+            self, cache::DEFAULT_CACHE_CAPACITY, flow::DEFAULT_BLOCK_FETCH_COST,
+            flow::DEFAULT_EVENTS_FETCH_COST, Error, FlowControlParams, FlowController,
+            VerifierCache,
+        },
+        Event, LightBlock, HEIGHT_LATEST,
+    },
+    protocol::Protocol,
+    types::{Body, EventKind, HostFetchConsensusEventsRequest, HostFetchConsensusEventsResponse},
+};
+
+/// The default trust threshold, expressed as a fraction of the trusted validator set's total
+/// voting power that must overlap with the target validator set for a skip to be accepted.
+pub const DEFAULT_TRUST_THRESHOLD: (u64, u64) = (1, 3);
+
+/// A trust anchor used to bootstrap the light client.
+#[derive(Clone, Debug)]
+pub struct TrustRoot {
+    /// Height of the trusted header.
+    pub height: u64,
+    /// Hash of the trusted header.
+    pub hash: Vec<u8>,
+}
+
+/// The light client's view of the consensus layer at a given point in time.
+struct TrustedState {
+    height: u64,
+    header_hash: Vec<u8>,
+    header_time: i64,
+    voting_power: u64,
+    validators: Vec<(Vec<u8>, u64)>,
+}
+
+/// A verifier which performs Tendermint bisection (skipping) verification against a configured
+/// trust anchor, as described by the core-verification spec.
+///
+/// Unlike [`crate::consensus::tendermint::verifier::NopVerifier`], this verifier never trusts a
+/// light block received from the host without first checking that it is reachable from the
+/// current trusted state via either an adjacent verification step or a validator-set skip.
+pub struct TrustedVerifier {
+    protocol: Arc<Protocol>,
+    /// The trust anchor the verifier was constructed with, retained for the lifetime of the
+    /// verifier so historical heights below the current watermark can still be re-verified from
+    /// scratch (see [`Self::advance_to`]) instead of being accepted on the host's word alone.
+    root: TrustRoot,
+    trust_threshold: (u64, u64),
+    trusting_period_secs: u64,
+    trusted: Mutex<Option<TrustedState>>,
+    cache: VerifierCache,
+    flow: FlowController,
+}
+
+impl TrustedVerifier {
+    /// Create a new trusted (skipping) verifier.
+    ///
+    /// The `trust_root` anchors the light client at a known-good height/header-hash pair,
+    /// `trust_threshold` is the fraction of overlapping trusted voting power required to accept
+    /// a skip (defaults to 1/3 when `None`) and `trusting_period_secs` bounds how old a trusted
+    /// header may be before it must be refreshed via an adjacent or bisected verification.
+    pub fn new(
+        protocol: Arc<Protocol>,
+        trust_root: TrustRoot,
+        trust_threshold: Option<(u64, u64)>,
+        trusting_period_secs: u64,
+    ) -> Self {
+        Self::with_params(
+            protocol,
+            trust_root,
+            trust_threshold,
+            trusting_period_secs,
+            DEFAULT_CACHE_CAPACITY,
+            FlowControlParams::default(),
+        )
+    }
+
+    /// Create a new trusted (skipping) verifier with a custom cache capacity and host fetch
+    /// flow-control parameters.
+    pub fn with_params(
+        protocol: Arc<Protocol>,
+        trust_root: TrustRoot,
+        trust_threshold: Option<(u64, u64)>,
+        trusting_period_secs: u64,
+        cache_capacity: usize,
+        flow_params: FlowControlParams,
+    ) -> Self {
+        Self {
+            protocol,
+            trusted: Mutex::new(Some(TrustedState {
+                height: trust_root.height,
+                header_hash: trust_root.hash.clone(),
+                header_time: 0,
+                voting_power: 0,
+                validators: Vec::new(),
+            })),
+            root: trust_root,
+            trust_threshold: trust_threshold.unwrap_or(DEFAULT_TRUST_THRESHOLD),
+            trusting_period_secs,
+            cache: VerifierCache::new(cache_capacity),
+            flow: FlowController::new(flow_params),
+        }
+    }
+
+    /// Start the trusted verifier.
+    pub fn start(&self) {
+        let logger = get_logger("consensus/cometbft/verifier");
+        info!(logger, "Starting consensus trusted verifier");
+    }
+
+    async fn fetch_light_block(&self, height: u64) -> Result<LightBlock, Error> {
+        if let Some(block) = self.cache.get_block(height).await {
+            return Ok(block);
+        }
+
+        self.flow.acquire(DEFAULT_BLOCK_FETCH_COST).await?;
+        let result = self
+            .protocol
+            .call_host_async(Body::HostFetchConsensusBlockRequest { height })
+            .await
+            .map_err(|err| Error::VerificationFailed(err.into()))?;
+
+        let block = match result {
+            Body::HostFetchConsensusBlockResponse { block } => block,
+            _ => return Err(Error::VerificationFailed(anyhow!("bad response from host"))),
+        };
+
+        self.cache.put_block(height, block.height, block.clone()).await;
+        Ok(block)
+    }
+
+    /// Verify that `target` can be reached from `trusted`, either adjacently or by bisection,
+    /// and return the new trusted state at the target height.
+    #[async_recursion::async_recursion]
+    async fn verify_to_target(
+        &self,
+        trusted: TrustedState,
+        target_height: u64,
+    ) -> Result<TrustedState, Error> {
+        let target = self.fetch_light_block(target_height).await?;
+        let decoded = decode_light_block(target.clone()).map_err(Error::VerificationFailed)?;
+
+        let target_time = decoded.header_time();
+        let now = decoded.verification_time();
+        if now.saturating_sub(target_time) > self.trusting_period_secs as i64 {
+            return Err(Error::HeaderExpired);
+        }
+        if trusted.header_time != 0 && target_time <= trusted.header_time {
+            return Err(Error::HeaderNotMonotonic);
+        }
+
+        let target_validators = decoded.validators();
+        let target_total_power: u64 = target_validators.iter().map(|(_, power)| power).sum();
+        let target_signed_power = decoded.commit_signed_power(&target_validators);
+
+        // Step (2): adjacent verification.
+        if target_height == trusted.height + 1 {
+            if decoded.last_block_hash() != trusted.header_hash {
+                return Err(Error::VerificationFailed(anyhow!(
+                    "adjacent header does not chain to trusted header"
+                )));
+            }
+            if target_signed_power * 3 <= target_total_power * 2 {
+                return Err(Error::InsufficientSignedPower);
+            }
+
+            return Ok(TrustedState {
+                height: target_height,
+                header_hash: decoded.header_hash(),
+                header_time: target_time,
+                voting_power: target_total_power,
+                validators: target_validators,
+            });
+        }
+
+        // Step (3): attempt a skip using the voting power of validators that are BOTH in the
+        // trusted validator set AND actually signed the target commit. Mere address membership
+        // in both sets is not enough: a target validator set can reuse trusted addresses without
+        // those validators having vouched for this particular commit, so the overlap must be
+        // filtered through `commit_signed_power` the same way `target_signed_power` is.
+        let overlapping_validators = Self::overlap(&trusted.validators, &target_validators);
+        let overlap_power = decoded.commit_signed_power(&overlapping_validators);
+
+        let (num, den) = self.trust_threshold;
+        let skip_ok = trusted.voting_power > 0
+            && overlap_power * den > trusted.voting_power * num
+            && target_signed_power * 3 > target_total_power * 2;
+
+        if skip_ok {
+            return Ok(TrustedState {
+                height: target_height,
+                header_hash: decoded.header_hash(),
+                header_time: target_time,
+                voting_power: target_total_power,
+                validators: target_validators,
+            });
+        }
+
+        // Step (4): bisect and recurse.
+        if target_height <= trusted.height + 1 {
+            return Err(Error::InsufficientOverlap);
+        }
+        let midpoint = (trusted.height + target_height) / 2;
+        let bisected = self.verify_to_target(trusted, midpoint).await?;
+        self.verify_to_target(bisected, target_height).await
+    }
+
+    /// The subset of `target` whose address also appears in `trusted`, keeping `target`'s power
+    /// entry (the one `commit_signed_power` will check signatures against for this commit).
+    fn overlap(trusted: &[(Vec<u8>, u64)], target: &[(Vec<u8>, u64)]) -> Vec<(Vec<u8>, u64)> {
+        target
+            .iter()
+            .filter(|(addr, _)| trusted.iter().any(|(a, _)| a == addr))
+            .cloned()
+            .collect()
+    }
+
+    async fn advance_to(&self, height: u64) -> Result<ConsensusState, Error> {
+        let height = match height {
+            HEIGHT_LATEST => self.latest_height().await?,
+            height => height,
+        };
+
+        if let Some(state) = self.cache.get_state(height).await {
+            return Ok(state);
+        }
+
+        let mut guard = self.trusted.lock().await;
+        let trusted = guard.take().ok_or(Error::TrustRootNotSet)?;
+
+        if height == trusted.height {
+            // The watermark itself was already verified (by an earlier `verify_to_target` call,
+            // or it is the trust root); just refetch the block for its state root.
+            *guard = Some(trusted);
+            let block = self.fetch_light_block(height).await?;
+            let decoded = decode_light_block(block).map_err(Error::VerificationFailed)?;
+            let state_root = decoded.get_state_root();
+            let state = ConsensusState::from_protocol(
+                self.protocol.clone(),
+                state_root.version + 1,
+                state_root,
+            );
+            self.cache.put_state(height, height, state.clone()).await;
+            return Ok(state);
+        }
+
+        if height < trusted.height {
+            // The watermark only vouches for blocks at or above its own height: it was verified
+            // by bisecting *forward* from some earlier anchor, which says nothing about whether
+            // an arbitrary earlier height's block is genuine. Accepting it on the host's word
+            // alone here would silently degrade every historical-replay lookup (`state_at`,
+            // `verify_for_query`, ... for old heights) to no better than an unverified client.
+            // Re-verify from the original trust root instead, which is retained exactly for this.
+            if height < self.root.height {
+                *guard = Some(trusted);
+                return Err(Error::VerificationFailed(anyhow!(
+                    "height {} is older than the configured trust root height {}",
+                    height,
+                    self.root.height
+                )));
+            }
+
+            let verified = if height == self.root.height {
+                let block = self.fetch_light_block(height).await?;
+                let decoded = decode_light_block(block).map_err(Error::VerificationFailed)?;
+                if decoded.header_hash() != self.root.hash {
+                    *guard = Some(trusted);
+                    return Err(Error::VerificationFailed(anyhow!(
+                        "block at trust root height does not match the configured trust root hash"
+                    )));
+                }
+                let validators = decoded.validators();
+                TrustedState {
+                    height,
+                    header_hash: decoded.header_hash(),
+                    header_time: decoded.header_time(),
+                    voting_power: validators.iter().map(|(_, power)| power).sum(),
+                    validators,
+                }
+            } else {
+                let root_anchor = TrustedState {
+                    height: self.root.height,
+                    header_hash: self.root.hash.clone(),
+                    header_time: 0,
+                    voting_power: 0,
+                    validators: Vec::new(),
+                };
+                match self.verify_to_target(root_anchor, height).await {
+                    Ok(verified) => verified,
+                    Err(err) => {
+                        *guard = Some(trusted);
+                        return Err(err);
+                    }
+                }
+            };
+
+            // Re-verifying from the root never moves the forward watermark backwards; restore it
+            // as-is once the historical height has been independently checked.
+            *guard = Some(trusted);
+            let block = self.fetch_light_block(verified.height).await?;
+            let decoded = decode_light_block(block).map_err(Error::VerificationFailed)?;
+            let state_root = decoded.get_state_root();
+            let state = ConsensusState::from_protocol(
+                self.protocol.clone(),
+                state_root.version + 1,
+                state_root,
+            );
+            self.cache.put_state(height, verified.height, state.clone()).await;
+            return Ok(state);
+        }
+
+        let advanced = self.verify_to_target(trusted, height).await?;
+        let block = self.fetch_light_block(advanced.height).await?;
+        let decoded = decode_light_block(block).map_err(Error::VerificationFailed)?;
+        let state_root = decoded.get_state_root();
+        let advanced_height = advanced.height;
+        *guard = Some(advanced);
+
+        let state = ConsensusState::from_protocol(
+            self.protocol.clone(),
+            state_root.version + 1,
+            state_root,
+        );
+        self.cache.put_state(height, advanced_height, state.clone()).await;
+        Ok(state)
+    }
+}
+
+#[async_trait]
+impl verifier::Verifier for TrustedVerifier {
+    async fn sync(&self, height: u64) -> Result<(), Error> {
+        self.cache.invalidate_latest().await;
+        self.advance_to(height).await?;
+        Ok(())
+    }
+
+    async fn verify(
+        &self,
+        consensus_block: LightBlock,
+        _runtime_header: Header,
+        _epoch: EpochTime,
+    ) -> Result<ConsensusState, Error> {
+        let decoded = decode_light_block(consensus_block).map_err(Error::VerificationFailed)?;
+        self.advance_to(decoded.height()).await
+    }
+
+    async fn verify_for_query(
+        &self,
+        consensus_block: LightBlock,
+        runtime_header: Header,
+        epoch: EpochTime,
+    ) -> Result<ConsensusState, Error> {
+        self.verify(consensus_block, runtime_header, epoch).await
+    }
+
+    async fn unverified_state(&self, consensus_block: LightBlock) -> Result<ConsensusState, Error> {
+        let decoded = decode_light_block(consensus_block).map_err(Error::VerificationFailed)?;
+        let state_root = decoded.get_state_root();
+        Ok(ConsensusState::from_protocol(
+            self.protocol.clone(),
+            state_root.version + 1,
+            state_root,
+        ))
+    }
+
+    async fn latest_state(&self) -> Result<ConsensusState, Error> {
+        self.advance_to(HEIGHT_LATEST).await
+    }
+
+    async fn state_at(&self, height: u64) -> Result<ConsensusState, Error> {
+        self.advance_to(height).await
+    }
+
+    async fn events_at(&self, height: u64, kind: EventKind) -> Result<Vec<Event>, Error> {
+        self.flow.acquire(DEFAULT_EVENTS_FETCH_COST).await?;
+        let result = self
+            .protocol
+            .call_host_async(Body::HostFetchConsensusEventsRequest(
+                HostFetchConsensusEventsRequest { height, kind },
+            ))
+            .await
+            .map_err(|err| Error::VerificationFailed(err.into()))?;
+
+        match result {
+            Body::HostFetchConsensusEventsResponse(HostFetchConsensusEventsResponse { events }) => {
+                Ok(events)
+            }
+            _ => Err(Error::VerificationFailed(anyhow!("bad response from host"))),
+        }
+    }
+
+    async fn latest_height(&self) -> Result<u64, Error> {
+        Ok(self.fetch_light_block(HEIGHT_LATEST).await?.height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TrustedVerifier;
+
+    #[test]
+    fn overlap_excludes_addresses_not_in_both_sets() {
+        let trusted = vec![(vec![1], 10), (vec![2], 20)];
+        let target = vec![(vec![2], 25), (vec![3], 30)];
+
+        // Only validator 2 is in both sets; validator 1 dropped out of the target set and
+        // validator 3 is new, so neither should count toward the overlap.
+        let overlap = TrustedVerifier::overlap(&trusted, &target);
+        assert_eq!(overlap, vec![(vec![2], 25)]);
+    }
+
+    #[test]
+    fn overlap_uses_target_power_not_trusted_power() {
+        // The overlap set must carry the *target* commit's power entries (what
+        // `commit_signed_power` checks signatures against), not the stale trusted-set power.
+        let trusted = vec![(vec![1], 100)];
+        let target = vec![(vec![1], 5)];
+
+        let overlap = TrustedVerifier::overlap(&trusted, &target);
+        assert_eq!(overlap, vec![(vec![1], 5)]);
+    }
+
+    #[test]
+    fn overlap_is_empty_when_no_addresses_match() {
+        let trusted = vec![(vec![1], 10)];
+        let target = vec![(vec![2], 10)];
+
+        assert!(TrustedVerifier::overlap(&trusted, &target).is_empty());
+    }
+}
@@ -0,0 +1,165 @@
+use std::thread;
+
+use tokio::sync::{mpsc, oneshot};
+
+use crate::consensus::{beacon::EpochTime, roothash::Header, state::ConsensusState, Event, LightBlock};
+
+use super::{Error, Verifier};
+
+/// Default size of the worker's command queue.
+pub const DEFAULT_QUEUE_SIZE: usize = 128;
+
+/// A command sent to the verifier worker thread.
+enum Command {
+    Sync(u64, oneshot::Sender<Result<(), Error>>),
+    Verify(
+        LightBlock,
+        Header,
+        EpochTime,
+        oneshot::Sender<Result<ConsensusState, Error>>,
+    ),
+    VerifyForQuery(
+        LightBlock,
+        Header,
+        EpochTime,
+        oneshot::Sender<Result<ConsensusState, Error>>,
+    ),
+    UnverifiedState(LightBlock, oneshot::Sender<Result<ConsensusState, Error>>),
+    LatestState(oneshot::Sender<Result<ConsensusState, Error>>),
+    StateAt(u64, oneshot::Sender<Result<ConsensusState, Error>>),
+    EventsAt(
+        u64,
+        crate::types::EventKind,
+        oneshot::Sender<Result<Vec<Event>, Error>>,
+    ),
+    LatestHeight(oneshot::Sender<Result<u64, Error>>),
+}
+
+/// A handle to a verifier running on a dedicated worker thread.
+///
+/// The handle itself implements [`Verifier`] by serializing each call into a [`Command`] sent
+/// over an mpsc channel and awaiting the reply on a oneshot channel, so verification work (which
+/// may include an expensive bisection) proceeds on the worker thread independently of whichever
+/// async task issued the request, and mutations of the wrapped verifier's trusted state are
+/// naturally serialized without locking from the caller's side.
+pub struct VerifierHandle {
+    commands: mpsc::Sender<Command>,
+}
+
+impl VerifierHandle {
+    /// Spawn `inner` on a dedicated worker thread and return a handle to it.
+    ///
+    /// `queue_size` bounds the number of in-flight requests; once full, further calls fail fast
+    /// with [`Error::WorkerBusy`] instead of blocking the caller indefinitely.
+    pub fn spawn(inner: Box<dyn Verifier>, queue_size: usize) -> Self {
+        let (tx, rx) = mpsc::channel(queue_size.max(1));
+
+        thread::Builder::new()
+            .name("cometbft-verifier".to_owned())
+            .spawn(move || Self::run(inner, rx))
+            .expect("failed to spawn verifier worker thread");
+
+        Self { commands: tx }
+    }
+
+    fn run(inner: Box<dyn Verifier>, mut rx: mpsc::Receiver<Command>) {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed to start verifier worker runtime");
+
+        rt.block_on(async move {
+            while let Some(cmd) = rx.recv().await {
+                match cmd {
+                    Command::Sync(height, reply) => {
+                        let _ = reply.send(inner.sync(height).await);
+                    }
+                    Command::Verify(block, header, epoch, reply) => {
+                        let _ = reply.send(inner.verify(block, header, epoch).await);
+                    }
+                    Command::VerifyForQuery(block, header, epoch, reply) => {
+                        let _ = reply.send(inner.verify_for_query(block, header, epoch).await);
+                    }
+                    Command::UnverifiedState(block, reply) => {
+                        let _ = reply.send(inner.unverified_state(block).await);
+                    }
+                    Command::LatestState(reply) => {
+                        let _ = reply.send(inner.latest_state().await);
+                    }
+                    Command::StateAt(height, reply) => {
+                        let _ = reply.send(inner.state_at(height).await);
+                    }
+                    Command::EventsAt(height, kind, reply) => {
+                        let _ = reply.send(inner.events_at(height, kind).await);
+                    }
+                    Command::LatestHeight(reply) => {
+                        let _ = reply.send(inner.latest_height().await);
+                    }
+                }
+            }
+            // All handles dropped; shut down cleanly.
+        });
+    }
+
+    async fn call<T>(
+        &self,
+        make_cmd: impl FnOnce(oneshot::Sender<Result<T, Error>>) -> Command,
+    ) -> Result<T, Error> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.commands
+            .try_send(make_cmd(reply_tx))
+            .map_err(|err| match err {
+                mpsc::error::TrySendError::Full(_) => Error::WorkerBusy,
+                mpsc::error::TrySendError::Closed(_) => Error::WorkerShutDown,
+            })?;
+        reply_rx.await.map_err(|_| Error::WorkerShutDown)?
+    }
+}
+
+#[async_trait::async_trait]
+impl Verifier for VerifierHandle {
+    async fn sync(&self, height: u64) -> Result<(), Error> {
+        self.call(|reply| Command::Sync(height, reply)).await
+    }
+
+    async fn verify(
+        &self,
+        consensus_block: LightBlock,
+        runtime_header: Header,
+        epoch: EpochTime,
+    ) -> Result<ConsensusState, Error> {
+        self.call(|reply| Command::Verify(consensus_block, runtime_header, epoch, reply))
+            .await
+    }
+
+    async fn verify_for_query(
+        &self,
+        consensus_block: LightBlock,
+        runtime_header: Header,
+        epoch: EpochTime,
+    ) -> Result<ConsensusState, Error> {
+        self.call(|reply| Command::VerifyForQuery(consensus_block, runtime_header, epoch, reply))
+            .await
+    }
+
+    async fn unverified_state(&self, consensus_block: LightBlock) -> Result<ConsensusState, Error> {
+        self.call(|reply| Command::UnverifiedState(consensus_block, reply))
+            .await
+    }
+
+    async fn latest_state(&self) -> Result<ConsensusState, Error> {
+        self.call(Command::LatestState).await
+    }
+
+    async fn state_at(&self, height: u64) -> Result<ConsensusState, Error> {
+        self.call(|reply| Command::StateAt(height, reply)).await
+    }
+
+    async fn events_at(&self, height: u64, kind: crate::types::EventKind) -> Result<Vec<Event>, Error> {
+        self.call(|reply| Command::EventsAt(height, kind, reply)).await
+    }
+
+    async fn latest_height(&self) -> Result<u64, Error> {
+        self.call(Command::LatestHeight).await
+    }
+}